@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+use std::time::{Duration, Instant};
+
+use crate::format::parse_text_object;
+use crate::{DatabaseLoadError, Object};
+
+/// Backs [`Database::with_capacity`]: tracks where each object lives in
+/// the backing (text-format) file and how recently each resident one was
+/// used, modeled on the morethantext `Cache`/`Entry` design.
+///
+/// [`Database::with_capacity`]: crate::Database::with_capacity
+#[derive(Debug)]
+pub(crate) struct ObjectCache {
+    capacity: usize,
+    offsets: HashMap<String, (u64, u64)>,
+    last_used: HashMap<String, Instant>,
+}
+
+impl ObjectCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            offsets: HashMap::new(),
+            last_used: HashMap::new(),
+        }
+    }
+
+    /// Records every object's byte span in `contents`, replacing whatever
+    /// was previously indexed.
+    pub(crate) fn index(&mut self, contents: &str) {
+        self.offsets = scan_offsets(contents);
+        self.last_used.clear();
+    }
+
+    /// Reads and parses the object named `name` directly from the file at
+    /// `path` using its indexed offset, without loading the rest of the
+    /// file. Returns `Ok(None)` if `name` wasn't indexed.
+    pub(crate) fn load_from_file(&self, path: &str, name: &str) -> Result<Option<Object>, DatabaseLoadError> {
+        let Some(&(start, end)) = self.offsets.get(name) else {
+            return Ok(None);
+        };
+
+        let mut file = std::fs::File::open(path).map_err(DatabaseLoadError::IO)?;
+        file.seek(SeekFrom::Start(start)).map_err(DatabaseLoadError::IO)?;
+        let mut block = vec![0u8; (end - start) as usize];
+        file.read_exact(&mut block).map_err(DatabaseLoadError::IO)?;
+
+        let block = String::from_utf8(block).map_err(|_| DatabaseLoadError::Corrupt)?;
+        let mut lines = block.lines();
+        lines.next(); // the "object=<name>" line itself; the name is already known
+        Ok(Some(parse_text_object(name.to_string(), &mut lines)?))
+    }
+
+    /// Every object name indexed by the last [`ObjectCache::index`] call,
+    /// resident or not. Lets [`Database::save`] merge evicted objects back
+    /// in instead of dropping them.
+    ///
+    /// [`Database::save`]: crate::Database::save
+    pub(crate) fn indexed_names(&self) -> impl Iterator<Item = &str> {
+        self.offsets.keys().map(String::as_str)
+    }
+
+    pub(crate) fn touch(&mut self, name: &str) {
+        self.last_used.insert(name.to_string(), Instant::now());
+    }
+
+    pub(crate) fn forget(&mut self, name: &str) {
+        self.last_used.remove(name);
+    }
+
+    /// The resident object [`Database::get_or_load_object`] should evict, if
+    /// `resident` holds more than `capacity` objects: the one with the
+    /// greatest `elapsed()` since it was last touched, other than `exclude`
+    /// (the object just requested, which must stay resident to be
+    /// returned). An object with no recorded use (just loaded, not yet
+    /// touched) counts as the oldest.
+    ///
+    /// [`Database::get_or_load_object`]: crate::Database::get_or_load_object
+    pub(crate) fn evict_candidate(&self, resident: &HashMap<String, Object>, exclude: &str) -> Option<String> {
+        if resident.len() <= self.capacity {
+            return None;
+        }
+        resident
+            .keys()
+            .filter(|name| name.as_str() != exclude)
+            .max_by_key(|name| self.last_used.get(name.as_str()).map(Instant::elapsed).unwrap_or(Duration::MAX))
+            .cloned()
+    }
+}
+
+/// Scans the text format's `object=<name>` blocks, recording the byte span
+/// of each (from the `object=` line up to the blank line `Display for
+/// Database` puts between objects, or end of file) so
+/// [`ObjectCache::load_from_file`] can re-read just that slice later.
+fn scan_offsets(contents: &str) -> HashMap<String, (u64, u64)> {
+    let mut offsets = HashMap::new();
+    let mut offset: u64 = 0;
+    let mut lines = contents.split('\n').peekable();
+
+    if let Some(header) = lines.next() {
+        offset += header.len() as u64 + 1;
+    }
+
+    while let Some(line) = lines.next() {
+        let object_start = offset;
+        offset += line.len() as u64 + 1;
+
+        let Some(name) = line.strip_prefix("object=") else {
+            continue;
+        };
+
+        while let Some(&next_line) = lines.peek() {
+            if next_line.is_empty() {
+                break;
+            }
+            offset += next_line.len() as u64 + 1;
+            lines.next();
+        }
+        offsets.insert(name.to_string(), (object_start, offset));
+    }
+
+    offsets
+}
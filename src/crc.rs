@@ -0,0 +1,44 @@
+use std::sync::OnceLock;
+
+const POLYNOMIAL: u32 = 0xEDB88320;
+
+/// Standard CRC-32 (IEEE 802.3) over `bytes`, used by [`Database::save`]/
+/// [`Database::load`] to detect truncated or bit-rotted files.
+///
+/// [`Database::save`]: crate::Database::save
+/// [`Database::load`]: crate::Database::load
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    static TABLE: OnceLock<[u32; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(build_table);
+
+    let mut crc = 0xFFFFFFFFu32;
+    for &byte in bytes {
+        let index = ((crc ^ byte as u32) & 0xFF) as usize;
+        crc = table[index] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFFFFFF
+}
+
+fn build_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ POLYNOMIAL } else { crc >> 1 };
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_known_vector() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::{Database, DatabaseLoadError, Object, Value};
+
+/// A pluggable on-disk encoding for a [`Database`].
+///
+/// `save`/`load` dispatch to one of these based on the `Database`'s
+/// [`FormatKind`]; a new encoding needs an impl of this trait plus a
+/// `FormatKind` variant and the matching arms in `save`/`load`.
+pub trait Format {
+    fn serialize(db: &Database) -> Vec<u8>;
+    fn deserialize(bytes: &[u8]) -> Result<Database, DatabaseLoadError>;
+}
+
+/// Which [`Format`] a [`Database`] reads and writes with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FormatKind {
+    #[default]
+    Text,
+    Json,
+    Binary,
+}
+
+/// The original line-based format written by `Display for Database`.
+pub struct TextFormat;
+
+impl Format for TextFormat {
+    fn serialize(db: &Database) -> Vec<u8> {
+        db.to_string().into_bytes()
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Database, DatabaseLoadError> {
+        let contents = String::from_utf8_lossy(bytes);
+        let mut database = Database::new(String::new());
+        let mut lines = contents.lines();
+        lines.next();
+
+        while let Some(line) = lines.next() {
+            if let Some(object_name) = line.strip_prefix("object=") {
+                database.add_object(parse_text_object(object_name.into(), &mut lines)?);
+            }
+        }
+
+        Ok(database)
+    }
+}
+
+/// Parses one `object=<name>` block's fields, given `lines` positioned
+/// just after the `object=<name>` line and running up to (and consuming)
+/// its `end` line. Shared by [`TextFormat::deserialize`] and the lazy
+/// per-object reads in [`crate::cache::ObjectCache`].
+pub(crate) fn parse_text_object<'a>(
+    name: String,
+    lines: &mut impl Iterator<Item = &'a str>,
+) -> Result<Object, DatabaseLoadError> {
+    let mut object = Object::new(name);
+    while let Some(object_line) = lines.next() {
+        if object_line == "end" {
+            break;
+        }
+        let object_line = &object_line[2..];
+
+        if let Some((value_name, value_value)) = object_line.split_once('=') {
+            object.add(value_name, value_value.try_into().map_err(DatabaseLoadError::ValueError)?);
+        }
+    }
+    Ok(object)
+}
+
+/// A serde-backed JSON encoding, interoperable with other tools that read
+/// the `Object`/`Value`/`Database` shapes as plain JSON.
+pub struct JsonFormat;
+
+impl Format for JsonFormat {
+    fn serialize(db: &Database) -> Vec<u8> {
+        serde_json::to_vec(db).expect("Database is always representable as JSON")
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Database, DatabaseLoadError> {
+        serde_json::from_slice(bytes).map_err(DatabaseLoadError::Json)
+    }
+}
+
+const BINARY_MAGIC: &[u8; 4] = b"SOSB";
+const BINARY_VERSION: u8 = 1;
+
+/// A compact, length/type-tagged binary encoding.
+///
+/// Unlike [`TextFormat`], field names and string values are stored raw
+/// (NUL- or length-delimited) instead of being split on `=`/newlines, so
+/// values containing those characters round-trip correctly. Layout:
+///
+/// ```text
+/// magic(4) version(1) object* end-of-file
+/// object   := name NUL field* NUL
+/// field    := field-name NUL tag(1) payload
+/// ```
+///
+/// where `tag` is one of `s`/`i`/`f`/`b`/`a`/`o` and `payload` is the
+/// matching [`Value`] encoding: for `a`, a little-endian u32 element count
+/// followed by that many `tag(1) payload` values; for `o`, a nested
+/// `object`.
+pub struct BinaryFormat;
+
+impl Format for BinaryFormat {
+    fn serialize(db: &Database) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(BINARY_MAGIC);
+        bytes.push(BINARY_VERSION);
+        for object in db.objects() {
+            write_object(object, &mut bytes);
+        }
+        bytes
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Database, DatabaseLoadError> {
+        let mut reader = Reader::new(bytes);
+        if reader.read_exact(4)? != BINARY_MAGIC.as_slice() {
+            return Err(DatabaseLoadError::Corrupt);
+        }
+        if reader.read_byte()? != BINARY_VERSION {
+            return Err(DatabaseLoadError::Corrupt);
+        }
+
+        let mut database = Database::new(String::new());
+        while !reader.is_empty() {
+            database.add_object(read_object(&mut reader)?);
+        }
+
+        Ok(database)
+    }
+}
+
+fn write_object(object: &Object, bytes: &mut Vec<u8>) {
+    bytes.extend_from_slice(object.name().as_bytes());
+    bytes.push(0);
+    for (field_name, value) in object.fields() {
+        bytes.extend_from_slice(field_name.as_bytes());
+        bytes.push(0);
+        write_value(value, bytes);
+    }
+    bytes.push(0);
+}
+
+fn read_object(reader: &mut Reader) -> Result<Object, DatabaseLoadError> {
+    let name = reader.read_cstr()?;
+    let mut object = Object::new(name);
+    loop {
+        let field_name = reader.read_cstr()?;
+        if field_name.is_empty() {
+            break;
+        }
+        object.add(&field_name, read_value(reader)?);
+    }
+    Ok(object)
+}
+
+fn write_value(value: &Value, bytes: &mut Vec<u8>) {
+    match value {
+        Value::Str(value) => {
+            bytes.push(b's');
+            bytes.extend_from_slice(&(value.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(value.as_bytes());
+        }
+        Value::Int(value) => {
+            bytes.push(b'i');
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        Value::Float(value) => {
+            bytes.push(b'f');
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        Value::Bool(value) => {
+            bytes.push(b'b');
+            bytes.push(*value as u8);
+        }
+        Value::Array(values) => {
+            bytes.push(b'a');
+            bytes.extend_from_slice(&(values.len() as u32).to_le_bytes());
+            for value in values {
+                write_value(value, bytes);
+            }
+        }
+        Value::Object(object) => {
+            bytes.push(b'o');
+            write_object(object, bytes);
+        }
+    }
+}
+
+fn read_value(reader: &mut Reader) -> Result<Value, DatabaseLoadError> {
+    match reader.read_byte()? {
+        b's' => {
+            let len = reader.read_u32()? as usize;
+            let bytes = reader.read_exact(len)?;
+            Ok(Value::Str(
+                String::from_utf8(bytes.to_vec()).map_err(|_| DatabaseLoadError::Corrupt)?,
+            ))
+        }
+        b'i' => Ok(Value::Int(i32::from_le_bytes(reader.read_exact(4)?.try_into().unwrap()))),
+        b'f' => Ok(Value::Float(f32::from_le_bytes(reader.read_exact(4)?.try_into().unwrap()))),
+        b'b' => Ok(Value::Bool(reader.read_byte()? != 0)),
+        b'a' => {
+            let len = reader.read_u32()? as usize;
+            let values = (0..len).map(|_| read_value(reader)).collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::Array(values))
+        }
+        b'o' => Ok(Value::Object(read_object(reader)?)),
+        _ => Err(DatabaseLoadError::Corrupt),
+    }
+}
+
+/// A cursor over the bytes being decoded, failing with
+/// [`DatabaseLoadError::Corrupt`] instead of panicking on short input.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn read_exact(&mut self, len: usize) -> Result<&'a [u8], DatabaseLoadError> {
+        let end = self.pos.checked_add(len).ok_or(DatabaseLoadError::Corrupt)?;
+        let slice = self.bytes.get(self.pos..end).ok_or(DatabaseLoadError::Corrupt)?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn read_byte(&mut self) -> Result<u8, DatabaseLoadError> {
+        Ok(self.read_exact(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, DatabaseLoadError> {
+        Ok(u32::from_le_bytes(self.read_exact(4)?.try_into().unwrap()))
+    }
+
+    fn read_cstr(&mut self) -> Result<String, DatabaseLoadError> {
+        let nul = self.bytes[self.pos..]
+            .iter()
+            .position(|&byte| byte == 0)
+            .ok_or(DatabaseLoadError::Corrupt)?;
+        let slice = self.read_exact(nul + 1)?;
+        String::from_utf8(slice[..nul].to_vec()).map_err(|_| DatabaseLoadError::Corrupt)
+    }
+}
@@ -1,15 +1,30 @@
+mod cache;
+mod crc;
+mod format;
+
 use std::{collections::HashMap, fmt::Display, fs::File, io::Write};
 
-#[derive(Debug)]
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use cache::ObjectCache;
+pub use format::{BinaryFormat, Format, FormatKind, JsonFormat, TextFormat};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Object {
     name: String,
     values: HashMap<String, Value>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Database {
     objects: HashMap<String, Object>,
+    #[serde(skip)]
     path: String,
+    #[serde(skip)]
+    format: FormatKind,
+    #[serde(skip)]
+    cache: Option<ObjectCache>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -18,15 +33,122 @@ pub enum Value {
     Int(i32),
     Float(f32),
     Bool(bool),
+    Array(Vec<Value>),
+    Object(Object),
+}
+
+/// Maps each variant to the native JSON type it represents (string, number,
+/// bool, array, object) instead of serde's default externally-tagged
+/// `{"Variant": ...}` encoding, so [`JsonFormat`]-written files are plain,
+/// interoperable JSON.
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Str(value) => serializer.serialize_str(value),
+            Value::Int(value) => serializer.serialize_i32(*value),
+            Value::Float(value) => serializer.serialize_f32(*value),
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::Array(values) => values.serialize(serializer),
+            Value::Object(object) => object.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a string, number, bool, array, or object")
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value, E> {
+                Ok(Value::Bool(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+                i32::try_from(value).map(Value::Int).map_err(|_| E::custom("integer out of range for Value::Int"))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+                i32::try_from(value).map(Value::Int).map_err(|_| E::custom("integer out of range for Value::Int"))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+                Ok(Value::Float(value as f32))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+                Ok(Value::Str(value.to_string()))
+            }
+
+            fn visit_string<E: de::Error>(self, value: String) -> Result<Value, E> {
+                Ok(Value::Str(value))
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(Value::Array(values))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Value, A::Error> {
+                let object = Object::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(Value::Object(object))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
 }
 
 impl Display for Value {
+    /// Top-level encoding, as used for a `field=value` line: a `Str`'s
+    /// payload is written raw. This must stay unescaped so databases
+    /// written before [`escape_str`] existed keep loading unchanged.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Value::Str(value) => write!(f, "s:{value}"),
-            Value::Int(value) => write!(f, "i:{value}"),
-            Value::Float(value) => write!(f, "f:{value}"),
-            Value::Bool(value) => write!(f, "b:{value}"),
+            other => write_nested(other, f),
+        }
+    }
+}
+
+/// Encodes `value` the way [`Display for Value`](Display) does, except a
+/// `Str`'s payload is escaped via [`escape_str`]. Used for array elements
+/// and object field values, which [`split_top_level`] scans for commas and
+/// nesting brackets — unlike a top-level `field=value` line, which never is.
+fn write_nested(value: &Value, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match value {
+        Value::Str(value) => write!(f, "s:{}", escape_str(value)),
+        Value::Int(value) => write!(f, "i:{value}"),
+        Value::Float(value) => write!(f, "f:{value}"),
+        Value::Bool(value) => write!(f, "b:{value}"),
+        Value::Array(values) => {
+            write!(f, "a:[")?;
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ",")?;
+                }
+                write_nested(value, f)?;
+            }
+            write!(f, "]")
+        }
+        Value::Object(object) => {
+            write!(f, "o:{}{{", object.name)?;
+            for (index, (name, value)) in object.values.iter().enumerate() {
+                if index > 0 {
+                    write!(f, ",")?;
+                }
+                write!(f, "{name}=")?;
+                write_nested(value, f)?;
+            }
+            write!(f, "}}")
         }
     }
 }
@@ -41,33 +163,127 @@ pub enum ValueParseError {
 impl TryFrom<&str> for Value {
     type Error = ValueParseError;
 
+    /// Parses a top-level `field=value` encoding, mirroring [`Display for
+    /// Value`](Display): a `Str`'s payload is taken raw, unescaped.
     fn try_from(str: &str) -> Result<Self, Self::Error> {
-        let mut chars = str.chars();
-        let type_hint = chars.next().ok_or(ValueParseError::ValueIsEmpty)?;
-        assert_eq!(':', chars.next().ok_or(ValueParseError::InvalidValue)?);
-        let value_str: String = chars.collect();
-        match type_hint {
-            's' => Ok(Value::Str(value_str)),
-            'i' => Ok(Value::Int(
-                value_str
-                    .parse()
-                    .map_err(|_| ValueParseError::InvalidValue)?,
-            )),
-            'f' => Ok(Value::Float(
-                value_str
-                    .parse()
-                    .map_err(|_| ValueParseError::InvalidValue)?,
-            )),
-            'b' => Ok(Value::Bool(
-                value_str
-                    .parse()
-                    .map_err(|_| ValueParseError::InvalidValue)?,
-            )),
-            _ => Err(ValueParseError::InvalidType),
+        parse_value(str, false)
+    }
+}
+
+/// Parses one [`Value`] encoding. `nested` selects [`write_nested`]'s
+/// escaped `Str` convention (used for array elements and object field
+/// values) over [`Display for Value`](Display)'s raw top-level one.
+fn parse_value(str: &str, nested: bool) -> Result<Value, ValueParseError> {
+    let mut chars = str.chars();
+    let type_hint = chars.next().ok_or(ValueParseError::ValueIsEmpty)?;
+    assert_eq!(':', chars.next().ok_or(ValueParseError::InvalidValue)?);
+    let value_str: String = chars.collect();
+    match type_hint {
+        's' => Ok(Value::Str(if nested { unescape_str(&value_str) } else { value_str })),
+        'i' => Ok(Value::Int(
+            value_str
+                .parse()
+                .map_err(|_| ValueParseError::InvalidValue)?,
+        )),
+        'f' => Ok(Value::Float(
+            value_str
+                .parse()
+                .map_err(|_| ValueParseError::InvalidValue)?,
+        )),
+        'b' => Ok(Value::Bool(
+            value_str
+                .parse()
+                .map_err(|_| ValueParseError::InvalidValue)?,
+        )),
+        'a' => {
+            let inner = value_str
+                .strip_prefix('[')
+                .and_then(|rest| rest.strip_suffix(']'))
+                .ok_or(ValueParseError::InvalidValue)?;
+            let values = split_top_level(inner)
+                .into_iter()
+                .map(|element| parse_value(element, true))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::Array(values))
         }
+        'o' => {
+            let (name, rest) = value_str.split_once('{').ok_or(ValueParseError::InvalidValue)?;
+            let rest = rest.strip_suffix('}').ok_or(ValueParseError::InvalidValue)?;
+            let mut object = Object::new(name.into());
+            for field in split_top_level(rest) {
+                let (field_name, field_value) = field.split_once('=').ok_or(ValueParseError::InvalidValue)?;
+                object.add(field_name, parse_value(field_value, true)?);
+            }
+            Ok(Value::Object(object))
+        }
+        _ => Err(ValueParseError::InvalidType),
     }
 }
 
+/// Splits `str` on top-level commas, treating `[`/`]` and `{`/`}` as nesting
+/// so a comma inside a nested array or object doesn't split it, and a
+/// backslash-escaped character (see [`escape_str`]) as neither a delimiter
+/// nor a nesting character. Returns no items for an empty string.
+fn split_top_level(str: &str) -> Vec<&str> {
+    if str.is_empty() {
+        return Vec::new();
+    }
+
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, char) in str.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match char {
+            '\\' => escaped = true,
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&str[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&str[start..]);
+    items
+}
+
+/// Escapes `,`/`[`/`]`/`{`/`}`/`\` in a [`Value::Str`] payload with a
+/// backslash, so the characters [`split_top_level`] treats specially can't
+/// be confused with literal ones when the string is nested in an array or
+/// object. Inverted by [`unescape_str`].
+fn escape_str(str: &str) -> String {
+    let mut escaped = String::with_capacity(str.len());
+    for char in str.chars() {
+        if matches!(char, '\\' | ',' | '[' | ']' | '{' | '}') {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
+
+/// Reverses [`escape_str`].
+fn unescape_str(str: &str) -> String {
+    let mut unescaped = String::with_capacity(str.len());
+    let mut chars = str.chars();
+    while let Some(char) = chars.next() {
+        if char == '\\' {
+            if let Some(escaped) = chars.next() {
+                unescaped.push(escaped);
+                continue;
+            }
+        }
+        unescaped.push(char);
+    }
+    unescaped
+}
+
 impl Object {
     pub fn new(name: String) -> Self {
         Self {
@@ -92,6 +308,14 @@ impl Object {
         self.add(name, value);
         self
     }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn fields(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.values.iter()
+    }
 }
 
 impl Display for Object {
@@ -100,7 +324,7 @@ impl Display for Object {
         for (name, value) in &self.values {
             writeln!(f, "  {name}={value}")?;
         }
-        Ok(())
+        writeln!(f, "end")
     }
 }
 
@@ -108,13 +332,47 @@ impl Display for Object {
 pub enum DatabaseLoadError {
     IO(std::io::Error),
     ValueError(ValueParseError),
+    Json(serde_json::Error),
+    Corrupt,
+    CorruptFile,
 }
 
 impl Database {
     pub fn new(path: String) -> Self {
+        Self::with_format(path, FormatKind::Text)
+    }
+
+    /// Like [`Database::new`], but selects the on-disk [`Format`] used by
+    /// [`Database::save`]/[`Database::load`] instead of defaulting to text.
+    pub fn with_format(path: String, format: FormatKind) -> Self {
         Self {
             objects: HashMap::new(),
             path,
+            format,
+            cache: None,
+        }
+    }
+
+    /// Like [`Database::new`], but [`Database::load`] only indexes where
+    /// each object lives in the (text-format) file instead of reading it
+    /// in full, and [`Database::get_or_load_object`] reads an object's
+    /// bytes from disk the first time it's asked for. Once more than
+    /// `max_objects` are resident, the least recently used one is dropped
+    /// to bound memory use on databases too large to hold entirely in
+    /// memory.
+    ///
+    /// Note this only indexes objects that are already on disk: objects
+    /// added afterward with [`Database::add_object`] and not yet `save`d
+    /// won't be picked up by `get_or_load_object`'s lazy path. `save` does
+    /// write back every indexed object, resident or not, so evicting one
+    /// doesn't drop it from the file — but [`Database::find`] and
+    /// [`Database::list_fields`] only see resident objects.
+    pub fn with_capacity(path: String, max_objects: usize) -> Self {
+        Self {
+            objects: HashMap::new(),
+            path,
+            format: FormatKind::Text,
+            cache: Some(ObjectCache::new(max_objects)),
         }
     }
 
@@ -126,43 +384,123 @@ impl Database {
         self.objects.remove(name.into())
     }
 
+    /// Returns the named object among those currently resident. With a
+    /// plain [`Database::new`]/[`Database::with_format`] database that's
+    /// every loaded object; with one built via [`Database::with_capacity`],
+    /// only objects [`Database::get_or_load_object`] has already faulted in
+    /// are resident, so prefer that method there.
     pub fn get_object(&self, name: &str) -> Option<&Object> {
-        self.objects.get(name.into())
+        self.objects.get(name)
+    }
+
+    /// Like [`Database::get_object`], but for a [`Database::with_capacity`]
+    /// database, also loads `name` from disk on demand if it isn't resident
+    /// and evicts the least recently used resident object to stay within
+    /// `max_objects`. Takes `&mut self` (unlike `get_object`) because that
+    /// on-demand load and eviction mutate which objects are resident.
+    pub fn get_or_load_object(&mut self, name: &str) -> Option<&Object> {
+        if !self.objects.contains_key(name) {
+            let cache = self.cache.as_ref()?;
+            let object = cache.load_from_file(&self.path, name).ok()??;
+            self.objects.insert(name.to_string(), object);
+        }
+
+        if let Some(cache) = self.cache.as_mut() {
+            cache.touch(name);
+            if let Some(evict_name) = cache.evict_candidate(&self.objects, name) {
+                self.objects.remove(&evict_name);
+                cache.forget(&evict_name);
+            }
+        }
+
+        self.objects.get(name)
+    }
+
+    pub(crate) fn objects(&self) -> impl Iterator<Item = &Object> {
+        self.objects.values()
+    }
+
+    /// Returns every object whose `field` is set to `value`.
+    ///
+    /// With a [`Database::with_capacity`] database, only objects already
+    /// resident (faulted in via [`Database::get_or_load_object`]) are
+    /// considered — this doesn't load the rest of the file, so results may
+    /// be partial.
+    pub fn find(&self, field: &str, value: &Value) -> Vec<&Object> {
+        self.objects
+            .values()
+            .filter(|object| object.get(field) == Some(value))
+            .collect()
+    }
+
+    /// Projects `fields` across every object, pairing each field with one
+    /// entry per object (`None` where that object doesn't have the field).
+    ///
+    /// With a [`Database::with_capacity`] database, only objects already
+    /// resident (faulted in via [`Database::get_or_load_object`]) are
+    /// projected — this doesn't load the rest of the file, so results may
+    /// be partial.
+    pub fn list_fields<'a>(&'a self, fields: &[&'a str]) -> Vec<(&'a str, Vec<Option<&'a Value>>)> {
+        fields
+            .iter()
+            .map(|&field| {
+                let values = self.objects.values().map(|object| object.get(field)).collect();
+                (field, values)
+            })
+            .collect()
     }
 
     pub fn save(&self) -> std::io::Result<()> {
+        let mut bytes = match (&self.cache, self.format) {
+            (Some(cache), FormatKind::Text) => self.serialize_text_with_non_resident(cache),
+            (None, FormatKind::Text) => TextFormat::serialize(self),
+            (_, FormatKind::Json) => JsonFormat::serialize(self),
+            (_, FormatKind::Binary) => BinaryFormat::serialize(self),
+        };
+        bytes.extend_from_slice(&crc::crc32(&bytes).to_le_bytes());
         let mut file = File::create(&self.path)?;
-        file.write_all(self.to_string().as_bytes())?;
+        file.write_all(&bytes)?;
         Ok(())
     }
 
-    pub fn load(&mut self) -> Result<(), DatabaseLoadError> {
-        let contents = std::fs::read_to_string(&self.path).map_err(|err| DatabaseLoadError::IO(err))?;
-        let mut lines = contents.lines();
-        lines.next();
-
-        while let Some(line) = lines.next() {
-            if let Some((_, object_name)) = line.split_once(" ") {
-                let mut object = Object::new(object_name.into());
-                while let Some(object_line) = lines.next() {
-                    if object_line == "end" {
-                        break;
-                    }
-                    let object_line = &object_line[2..];
-
-                    if let Some((value_name, value_value)) = object_line.split_once("=") {
-                        object.add(
-                            value_name,
-                            value_value
-                                .try_into()
-                                .map_err(|err| DatabaseLoadError::ValueError(err))?,
-                        );
-                    }
+    /// Like [`TextFormat::serialize`], but for a [`Database::with_capacity`]
+    /// database: appends the raw text of every object `cache` indexed from
+    /// the last [`Database::load`] that isn't currently resident, so an
+    /// object evicted by [`Database::get_or_load_object`] (or never faulted
+    /// in at all) isn't silently dropped from the file by `save`.
+    fn serialize_text_with_non_resident(&self, cache: &ObjectCache) -> Vec<u8> {
+        let mut text = self.to_string();
+        for name in cache.indexed_names() {
+            if !self.objects.contains_key(name) {
+                if let Some(object) = cache.load_from_file(&self.path, name).ok().flatten() {
+                    text.push_str(&object.to_string());
                 }
-                self.add_object(object);
             }
         }
+        text.into_bytes()
+    }
 
+    pub fn load(&mut self) -> Result<(), DatabaseLoadError> {
+        let mut bytes = std::fs::read(&self.path).map_err(DatabaseLoadError::IO)?;
+        let checksum_offset = bytes.len().checked_sub(4).ok_or(DatabaseLoadError::CorruptFile)?;
+        let stored_checksum = u32::from_le_bytes(bytes[checksum_offset..].try_into().unwrap());
+        bytes.truncate(checksum_offset);
+        if crc::crc32(&bytes) != stored_checksum {
+            return Err(DatabaseLoadError::CorruptFile);
+        }
+
+        if let Some(cache) = self.cache.as_mut() {
+            let contents = String::from_utf8(bytes).map_err(|_| DatabaseLoadError::Corrupt)?;
+            cache.index(&contents);
+            return Ok(());
+        }
+
+        let loaded = match self.format {
+            FormatKind::Text => TextFormat::deserialize(&bytes)?,
+            FormatKind::Json => JsonFormat::deserialize(&bytes)?,
+            FormatKind::Binary => BinaryFormat::deserialize(&bytes)?,
+        };
+        self.objects.extend(loaded.objects);
         Ok(())
     }
 }
@@ -193,4 +531,242 @@ mod tests {
         let value2: Value = value_str.try_into().unwrap();
         assert_eq!(value2, value);
     }
+
+    #[test]
+    fn top_level_string_value_is_written_and_read_raw() {
+        // A top-level `field=value` line is never scanned by
+        // `split_top_level`, so it must stay unescaped for databases
+        // written before escaping existed to keep loading unchanged.
+        let value = Value::Str(r"C:\Users\alice,nyc[1]".into());
+        let value_str = value.to_string();
+        assert_eq!(value_str, r"s:C:\Users\alice,nyc[1]");
+        let value2: Value = value_str.as_str().try_into().unwrap();
+        assert_eq!(value2, value);
+    }
+
+    #[test]
+    fn array_and_object_values_round_trip_through_display() {
+        let array = Value::Array(vec![Value::Int(1), Value::Int(2)]);
+        assert_eq!(array.to_string(), "a:[i:1,i:2]");
+        let array2: Value = array.to_string().as_str().try_into().unwrap();
+        assert_eq!(array2, array);
+
+        let nested = Value::Object(Object::new("point".into()).with_value("x", Value::Int(1)));
+        let nested2: Value = nested.to_string().as_str().try_into().unwrap();
+        assert_eq!(nested2, nested);
+    }
+
+    #[test]
+    fn array_value_with_special_characters_in_string_round_trips() {
+        let array = Value::Array(vec![Value::Str("a,b".into()), Value::Str("[x]{y}".into()), Value::Int(2)]);
+        let array2: Value = array.to_string().as_str().try_into().unwrap();
+        assert_eq!(array2, array);
+    }
+
+    #[test]
+    fn find_and_list_fields() {
+        let mut database = Database::new("test.sosdb".into());
+        database.add_object(Object::new("alice".into()).with_value("role", Value::Str("admin".into())));
+        database.add_object(Object::new("bob".into()).with_value("role", Value::Str("user".into())));
+        database.add_object(Object::new("carol".into()).with_value("role", Value::Str("admin".into())));
+
+        let admins = database.find("role", &Value::Str("admin".into()));
+        let mut names: Vec<&str> = admins.iter().map(|object| object.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["alice", "carol"]);
+
+        let projection = database.list_fields(&["role", "missing"]);
+        assert_eq!(projection.len(), 2);
+        let (field, values) = &projection[0];
+        assert_eq!(*field, "role");
+        assert_eq!(values.len(), 3);
+        let (field, values) = &projection[1];
+        assert_eq!(*field, "missing");
+        assert!(values.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn text_round_trip_through_public_api() {
+        let path = std::env::temp_dir().join("sosdb_text_round_trip_test.sosdb");
+        let path = path.to_str().unwrap().to_string();
+
+        let mut database = Database::new(path.clone());
+        database.add_object(Object::new("alice".into()).with_value("age", Value::Int(30)));
+        database.add_object(Object::new("bob".into()).with_value("age", Value::Int(40)));
+        database.save().unwrap();
+
+        let mut reloaded = Database::new(path.clone());
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.get_object("alice").unwrap().get("age"), Some(&Value::Int(30)));
+        assert_eq!(reloaded.get_object("bob").unwrap().get("age"), Some(&Value::Int(40)));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let mut database = Database::with_format("test.json".into(), FormatKind::Json);
+        database.add_object(
+            Object::new("alice".into())
+                .with_value("age", Value::Int(30))
+                .with_value("active", Value::Bool(true))
+                .with_value("tags", Value::Array(vec![Value::Str("a".into())])),
+        );
+
+        let bytes = JsonFormat::serialize(&database);
+        // Values serialize as native JSON, not `{"Int":30}`-style externally
+        // tagged variants, so other tools can read these files as plain JSON.
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        assert!(text.contains("\"age\":30"), "{text}");
+        assert!(text.contains("\"active\":true"), "{text}");
+        assert!(!text.contains("\"Int\""));
+
+        let restored = JsonFormat::deserialize(&bytes).unwrap();
+        assert_eq!(restored.get_object("alice").unwrap().get("age"), Some(&Value::Int(30)));
+        assert_eq!(restored.get_object("alice").unwrap().get("active"), Some(&Value::Bool(true)));
+        assert_eq!(
+            restored.get_object("alice").unwrap().get("tags"),
+            Some(&Value::Array(vec![Value::Str("a".into())]))
+        );
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let mut database = Database::with_format("test.sosb".into(), FormatKind::Binary);
+        database.add_object(
+            Object::new("alice".into())
+                .with_value("bio", Value::Str("loves = signs\nand newlines".into()))
+                .with_value("age", Value::Int(30))
+                .with_value("active", Value::Bool(true))
+                .with_value("tags", Value::Array(vec![Value::Str("a".into()), Value::Str("b".into())]))
+                .with_value("address", Value::Object(Object::new("address".into()).with_value("city", Value::Str("nyc".into())))),
+        );
+
+        let bytes = BinaryFormat::serialize(&database);
+        let restored = BinaryFormat::deserialize(&bytes).unwrap();
+        let alice = restored.get_object("alice").unwrap();
+        assert_eq!(alice.get("bio"), Some(&Value::Str("loves = signs\nand newlines".into())));
+        assert_eq!(alice.get("age"), Some(&Value::Int(30)));
+        assert_eq!(alice.get("active"), Some(&Value::Bool(true)));
+        assert_eq!(
+            alice.get("tags"),
+            Some(&Value::Array(vec![Value::Str("a".into()), Value::Str("b".into())]))
+        );
+        assert_eq!(
+            alice.get("address"),
+            Some(&Value::Object(Object::new("address".into()).with_value("city", Value::Str("nyc".into()))))
+        );
+
+        assert!(matches!(BinaryFormat::deserialize(b"nope"), Err(DatabaseLoadError::Corrupt)));
+    }
+
+    #[test]
+    fn save_load_detects_corruption() {
+        let path = std::env::temp_dir().join("sosdb_crc_test.sosdb");
+        let path = path.to_str().unwrap().to_string();
+
+        let mut database = Database::with_format(path.clone(), FormatKind::Binary);
+        database.add_object(Object::new("alice".into()).with_value("age", Value::Int(30)));
+        database.save().unwrap();
+
+        let mut reloaded = Database::with_format(path.clone(), FormatKind::Binary);
+        reloaded.load().unwrap();
+        assert_eq!(reloaded.get_object("alice").unwrap().get("age"), Some(&Value::Int(30)));
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        std::fs::write(&path, &bytes).unwrap();
+
+        let mut corrupted = Database::with_format(path.clone(), FormatKind::Binary);
+        assert!(matches!(corrupted.load(), Err(DatabaseLoadError::CorruptFile)));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn with_capacity_zero_evicts_the_previously_requested_object() {
+        let path = std::env::temp_dir().join("sosdb_cache_zero_test.sosdb");
+        let path = path.to_str().unwrap().to_string();
+
+        let mut database = Database::with_capacity(path.clone(), 0);
+        for name in ["a", "b"] {
+            database.add_object(Object::new(name.into()).with_value("n", Value::Str(name.into())));
+        }
+        database.save().unwrap();
+
+        let mut reloaded = Database::with_capacity(path.clone(), 0);
+        reloaded.load().unwrap();
+
+        assert_eq!(reloaded.get_or_load_object("a").unwrap().get("n"), Some(&Value::Str("a".into())));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        // "a" is the only resident object and was just touched, but capacity
+        // 0 still must not let residency grow past what's needed to answer.
+        assert_eq!(reloaded.get_or_load_object("b").unwrap().get("n"), Some(&Value::Str("b".into())));
+        assert_eq!(reloaded.objects.len(), 1);
+        assert!(!reloaded.objects.contains_key("a"));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn with_capacity_lazily_loads_and_evicts() {
+        let path = std::env::temp_dir().join("sosdb_cache_test.sosdb");
+        let path = path.to_str().unwrap().to_string();
+
+        let mut database = Database::with_capacity(path.clone(), 2);
+        for name in ["a", "b", "c"] {
+            database.add_object(Object::new(name.into()).with_value("n", Value::Str(name.into())));
+        }
+        database.save().unwrap();
+
+        let mut reloaded = Database::with_capacity(path.clone(), 2);
+        reloaded.load().unwrap();
+        assert!(reloaded.objects.is_empty(), "load() should only index offsets, not read objects eagerly");
+
+        assert_eq!(reloaded.get_or_load_object("a").unwrap().get("n"), Some(&Value::Str("a".into())));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(reloaded.get_or_load_object("b").unwrap().get("n"), Some(&Value::Str("b".into())));
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert_eq!(reloaded.objects.len(), 2);
+
+        // "a" is now the least recently used resident object, so loading "c" evicts it.
+        assert_eq!(reloaded.get_or_load_object("c").unwrap().get("n"), Some(&Value::Str("c".into())));
+        assert_eq!(reloaded.objects.len(), 2);
+        assert!(!reloaded.objects.contains_key("a"));
+
+        // Evicted objects are still on disk and reloadable on demand.
+        assert_eq!(reloaded.get_or_load_object("a").unwrap().get("n"), Some(&Value::Str("a".into())));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn with_capacity_save_preserves_evicted_objects() {
+        let path = std::env::temp_dir().join("sosdb_cache_save_test.sosdb");
+        let path = path.to_str().unwrap().to_string();
+
+        let mut database = Database::with_capacity(path.clone(), 1);
+        for name in ["a", "b"] {
+            database.add_object(Object::new(name.into()).with_value("n", Value::Str(name.into())));
+        }
+        database.save().unwrap();
+
+        let mut reloaded = Database::with_capacity(path.clone(), 1);
+        reloaded.load().unwrap();
+        reloaded.get_or_load_object("a").unwrap();
+        // Faulting in "b" evicts "a", since capacity is 1.
+        assert_eq!(reloaded.get_or_load_object("b").unwrap().get("n"), Some(&Value::Str("b".into())));
+        assert!(!reloaded.objects.contains_key("a"));
+
+        // Saving now must not drop "a" from the file just because it isn't resident.
+        reloaded.save().unwrap();
+
+        let mut verify = Database::with_capacity(path.clone(), 2);
+        verify.load().unwrap();
+        assert_eq!(verify.get_or_load_object("a").unwrap().get("n"), Some(&Value::Str("a".into())));
+        assert_eq!(verify.get_or_load_object("b").unwrap().get("n"), Some(&Value::Str("b".into())));
+
+        std::fs::remove_file(&path).ok();
+    }
 }